@@ -1,46 +1,225 @@
 use nom::{
-    bytes::complete::is_not, character::complete::alpha1, character::complete::multispace0,
-    character::complete::space1, error::make_error, error::ParseError, sequence::delimited,
-    Err::Error as NomError, IResult,
+    bytes::complete::is_not, bytes::complete::take_while1,
+    character::complete::alpha1,
+    character::complete::char as char1, character::complete::multispace0,
+    character::complete::space1, combinator::all_consuming, combinator::opt,
+    combinator::recognize, error::context, error::convert_error, error::ContextError,
+    error::ParseError, error::VerboseError, multi::separated_list0, multi::separated_list1,
+    sequence::delimited, sequence::preceded, sequence::tuple, Err::Error as NomError, IResult,
 };
 
+use std::fmt;
+
 pub struct Dockerfile<'a> {
     pub instructions: Vec<Instruction<'a>>,
 }
 
+/// A syntax error encountered while parsing a whole Dockerfile.
+///
+/// Produced by [`Dockerfile::parse`], which keeps parsing subsequent
+/// instructions after a bad line rather than aborting.
+#[derive(Debug, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub line: usize,
+    pub span: (usize, usize),
+}
+
+impl<'a> Dockerfile<'a> {
+    /// Parses every instruction in `input`, recovering from unrecognised
+    /// lines instead of aborting on the first one.
+    ///
+    /// When a line doesn't match a known instruction, a [`SyntaxError`] is
+    /// recorded, parsing resumes at the next line, and all successfully
+    /// parsed instructions (before and after the bad line) are still
+    /// returned.
+    /// ```rust
+    /// # use dockerfile_parser::Dockerfile;
+    /// let input = "FROM ubuntu:test\nNOTANINSTRUCTION\nRUN echo hi\n";
+    /// let (dockerfile, errors) = Dockerfile::parse(input);
+    /// assert_eq!(dockerfile.instructions.len(), 2);
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].line, 2);
+    /// ```
+    pub fn parse(input: &'a str) -> (Dockerfile<'a>, Vec<SyntaxError>) {
+        let mut instructions = Vec::new();
+        let mut errors = Vec::new();
+        let mut rem = input;
+        loop {
+            let trimmed = rem.trim_start();
+            if trimmed.is_empty() {
+                break;
+            }
+            if trimmed.starts_with('#') {
+                let start = input.len() - trimmed.len();
+                let end = start + logical_line_end(trimmed);
+                rem = if end < input.len() {
+                    &input[end + 1..]
+                } else {
+                    ""
+                };
+                continue;
+            }
+            match parse_instruction(rem) {
+                Ok((next, instruction)) => {
+                    instructions.push(instruction);
+                    rem = next;
+                }
+                Err(_) => {
+                    let start = input.len() - trimmed.len();
+                    let end = start + logical_line_end(trimmed);
+                    let line = input[..start].matches('\n').count() + 1;
+                    let bad_line = trimmed[..end - start].trim_end_matches('\r');
+                    let keyword = bad_line.split_whitespace().next().unwrap_or(bad_line);
+                    let is_known_instruction =
+                        matches!(keyword.to_ascii_lowercase().as_str(), "from" | "run");
+                    let message = if is_known_instruction {
+                        match parse_instruction_verbose(bad_line) {
+                            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                                format_verbose_error(bad_line, e)
+                            }
+                            _ => format!("invalid `{}` instruction", keyword),
+                        }
+                    } else {
+                        format!("unknown instruction `{}`", keyword)
+                    };
+                    errors.push(SyntaxError {
+                        message,
+                        line,
+                        span: (start, end),
+                    });
+                    rem = if end < input.len() {
+                        &input[end + 1..]
+                    } else {
+                        ""
+                    };
+                }
+            }
+        }
+        (Dockerfile { instructions }, errors)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Instruction<'a> {
     From(From<'a>),
     Run(Run<'a>),
 }
 
+impl<'a> fmt::Display for Instruction<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::From(from) => from.fmt(f),
+            Instruction::Run(run) => run.fmt(f),
+        }
+    }
+}
+
+/// A parsed `FROM` image reference, e.g.
+/// `registry.example.com:5000/foo/bar:1.0@sha256:abcd`.
+///
+/// The reference grammar mirrors the one used by OCI/Docker: an optional
+/// registry host (with an optional `:port`), a `/`-separated repository
+/// path, an optional `:tag` and an optional `@algorithm:hex` digest.
+#[derive(Debug, PartialEq)]
+pub struct ImageRef<'a> {
+    /// The raw, unparsed reference as it appeared in the Dockerfile.
+    pub raw: &'a str,
+    pub registry: Option<&'a str>,
+    pub repository: &'a str,
+    pub tag: Option<&'a str>,
+    pub digest: Option<&'a str>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct From<'a> {
-    pub image: &'a str,
+    pub image: ImageRef<'a>,
+    /// The build stage name from an optional `AS <name>` clause, e.g.
+    /// `builder` in `FROM golang:1.21 AS builder`.
+    pub stage: Option<&'a str>,
 }
 
 impl<'a> From<'a> {
-    pub fn new(image: &'a str) -> Self {
-        Self { image }
+    pub fn new(image: ImageRef<'a>, stage: Option<&'a str>) -> Self {
+        Self { image, stage }
+    }
+}
+
+impl<'a> fmt::Display for ImageRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(registry) = self.registry {
+            write!(f, "{}/", registry)?;
+        }
+        write!(f, "{}", self.repository)?;
+        if let Some(tag) = self.tag {
+            write!(f, ":{}", tag)?;
+        }
+        if let Some(digest) = self.digest {
+            write!(f, "@{}", digest)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for From<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FROM {}", self.image)?;
+        if let Some(stage) = self.stage {
+            write!(f, " AS {}", stage)?;
+        }
+        Ok(())
+    }
+}
+
+/// The body of a `RUN` instruction, in either of the two forms Docker
+/// supports: the shell form (a single string passed to `/bin/sh -c`) or the
+/// exec form (a JSON array of arguments run directly, without a shell).
+#[derive(Debug, PartialEq)]
+pub enum RunCommand<'a> {
+    Shell(&'a str),
+    Exec(Vec<String>),
+}
+
+impl<'a> fmt::Display for RunCommand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunCommand::Shell(command) => write!(f, "{}", command),
+            RunCommand::Exec(args) => {
+                write!(f, "[")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}", arg)?;
+                }
+                write!(f, "]")
+            }
+        }
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Run<'a> {
-    pub command: &'a str,
+    pub command: RunCommand<'a>,
 }
 
 impl<'a> Run<'a> {
-    pub fn new(command: &'a str) -> Self {
+    pub fn new(command: RunCommand<'a>) -> Self {
         Self { command }
     }
 }
 
+impl<'a> fmt::Display for Run<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RUN {}", self.command)
+    }
+}
+
 /// Parse a single instruction.
 ///
 /// Given an input string, parses the first instruction encountered.
 /// ```rust
-/// # use dockerfile_parser::{parse_instruction, Instruction, From, Run};
+/// # use dockerfile_parser::{parse_instruction, Instruction, From, Run, RunCommand};
 /// # use nom::{
 /// #     bytes::complete::is_not, bytes::complete::tag, character::complete::multispace0,
 /// #     character::complete::space1, error::ParseError, sequence::delimited, IResult,
@@ -56,8 +235,10 @@ impl<'a> Run<'a> {
 ///
 /// match (from_instruction, run_instruction) {
 ///     (Instruction::From(from), Instruction::Run(run)) => {
-///         assert_eq!(from.image, "ubuntu:test");
-///         assert_eq!(run.command, r#"/bin/bash -c echo "test""#);
+///         assert_eq!(from.image.registry, None);
+///         assert_eq!(from.image.repository, "ubuntu");
+///         assert_eq!(from.image.tag, Some("test"));
+///         assert_eq!(run.command, RunCommand::Shell(r#"/bin/bash -c echo "test""#));
 ///     }
 ///     _ => panic!("Didn't parse instructions correctly!"),
 /// }
@@ -65,49 +246,276 @@ impl<'a> Run<'a> {
 /// # }
 /// ```
 pub fn parse_instruction(input: &str) -> IResult<&str, Instruction<'_>> {
-    let (rem, instruction): (&str, &str) = delimited(multispace0, alpha1, space1)(input)?;
+    parse_instruction_impl(input)
+}
+
+/// Parses a single instruction like [`parse_instruction`], but accumulates
+/// [`VerboseError`] context as it goes, so a failure can be rendered with
+/// [`format_verbose_error`] into a message pointing at the offending span.
+/// ```rust
+/// # use dockerfile_parser::{format_verbose_error, parse_instruction_verbose};
+/// let input = "FROM \n";
+/// match parse_instruction_verbose(input) {
+///     Ok(_) => panic!("expected a parse failure"),
+///     Err(nom::Err::Error(error)) | Err(nom::Err::Failure(error)) => {
+///         let message = format_verbose_error(input, error);
+///         assert!(message.contains("image name"));
+///     }
+///     Err(nom::Err::Incomplete(_)) => panic!("unexpected incomplete parse"),
+/// }
+/// ```
+pub fn parse_instruction_verbose(
+    input: &str,
+) -> IResult<&str, Instruction<'_>, VerboseError<&str>> {
+    parse_instruction_impl(input)
+}
+
+/// Renders a [`VerboseError`] returned by [`parse_instruction_verbose`] into
+/// a human-readable, multi-line message pointing at the offending span,
+/// along with the chain of contexts that were being parsed.
+pub fn format_verbose_error(input: &str, error: VerboseError<&str>) -> String {
+    convert_error(input, error)
+}
+
+fn parse_instruction_impl<'a, E>(input: &'a str) -> IResult<&'a str, Instruction<'a>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str> + 'a,
+{
+    let (rem, instruction): (&str, &str) =
+        context("instruction keyword", delimited(multispace0, alpha1, space1))(input)?;
     // instruction names are all ASCII (are they???), this is much faster than `to_lowercase()`.
     match instruction.to_ascii_lowercase().as_str() {
         <From>::NAME => Ok(<From>::parse(rem)?),
         <Run>::NAME => Ok(<Run>::parse(rem)?),
-        _ => Err(NomError(make_error(rem, nom::error::ErrorKind::Tag))),
+        _ => Err(NomError(E::from_error_kind(rem, nom::error::ErrorKind::Tag))),
     }
 }
 
 trait InstructionParser {
     const NAME: &'static str;
 
-    fn parse(input: &str) -> IResult<&str, Instruction<'_>>;
+    fn parse<'a, E>(input: &'a str) -> IResult<&'a str, Instruction<'a>, E>
+    where
+        E: ParseError<&'a str> + ContextError<&'a str> + 'a;
 }
 
 impl InstructionParser for From<'_> {
     const NAME: &'static str = "from";
 
-    fn parse(input: &str) -> IResult<&str, Instruction<'_>> {
-        let (rem, image) = ws(is_not_newline())(input)?;
-        Ok((rem, Instruction::From(From::new(image))))
+    fn parse<'a, E>(input: &'a str) -> IResult<&'a str, Instruction<'a>, E>
+    where
+        E: ParseError<&'a str> + ContextError<&'a str> + 'a,
+    {
+        let (rem, image) = context("image name", ws(image_ref()))(input)?;
+        let (rem, stage) = opt(context("stage alias", stage_alias()))(rem)?;
+        Ok((rem, Instruction::From(From::new(image, stage))))
     }
 }
 
 impl InstructionParser for Run<'_> {
     const NAME: &'static str = "run";
 
-    fn parse(input: &str) -> IResult<&str, Instruction<'_>> {
-        let (rem, image) = ws(is_not_newline())(input)?;
-        Ok((rem, Instruction::Run(Run::new(image))))
+    fn parse<'a, E>(input: &'a str) -> IResult<&'a str, Instruction<'a>, E>
+    where
+        E: ParseError<&'a str> + ContextError<&'a str> + 'a,
+    {
+        let (rem, body) = context("run command", ws(logical_line()))(input)?;
+        let body = body.trim();
+        let command = if body.starts_with('[') {
+            let (_, args) =
+                context("exec form arguments", all_consuming(exec_form::<E>()))(body)?;
+            RunCommand::Exec(args)
+        } else {
+            RunCommand::Shell(body)
+        };
+        Ok((rem, Instruction::Run(Run::new(command))))
+    }
+}
+
+/// Scans from the start of `input` for the end of a single logical line: the
+/// byte offset of the next newline that isn't escaped by a trailing `\`, or
+/// `input.len()` if there is none. A trailing `\r` before that newline is
+/// excluded. Shared by [`logical_line`] and [`Dockerfile::parse`]'s recovery
+/// so both treat a continued `RUN` as one line.
+fn logical_line_end(input: &str) -> usize {
+    let bytes = input.as_bytes();
+    let mut scan = 0;
+    loop {
+        match input[scan..].find('\n') {
+            None => return input.len(),
+            Some(rel) => {
+                let newline_at = scan + rel;
+                let before_newline = if newline_at > 0 && bytes[newline_at - 1] == b'\r' {
+                    newline_at - 1
+                } else {
+                    newline_at
+                };
+                if before_newline > 0 && bytes[before_newline - 1] == b'\\' {
+                    scan = newline_at + 1;
+                } else {
+                    return before_newline;
+                }
+            }
+        }
     }
 }
 
-fn is_not_newline<'a, E: ParseError<&'a str>>(
+/// Consumes a single logical line: everything up to (but not including) the
+/// next newline that isn't escaped by a trailing `\`. An escaped newline -
+/// Docker's line continuation - is absorbed into the captured span so a
+/// multi-line `RUN` is still returned as a single instruction.
+fn logical_line<'a, E: ParseError<&'a str>>(
 ) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
-    is_not("\r\n")
+    move |input: &'a str| {
+        let content_end = logical_line_end(input);
+        if content_end == 0 {
+            return Err(NomError(E::from_error_kind(
+                input,
+                nom::error::ErrorKind::TakeWhile1,
+            )));
+        }
+        Ok((&input[content_end..], &input[..content_end]))
+    }
+}
+
+/// Parses the exec form of a `RUN` instruction: a JSON array of
+/// double-quoted arguments, e.g. `["/bin/bash", "-c", "echo hi"]`.
+fn exec_form<'a, E: ParseError<&'a str> + 'a>(
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<String>, E> {
+    delimited(
+        char1('['),
+        separated_list0(ws(char1(',')), ws(quoted_string())),
+        char1(']'),
+    )
+}
+
+/// Parses a JSON-style quoted string, unescaping `\"` and `\\` as it goes.
+fn quoted_string<'a, E: ParseError<&'a str>>(
+) -> impl FnMut(&'a str) -> IResult<&'a str, String, E> {
+    move |input: &'a str| {
+        let (mut rest, _) = char1('"')(input)?;
+        let mut value = String::new();
+        loop {
+            match rest.chars().next() {
+                Some('"') => return Ok((&rest[1..], value)),
+                Some('\\') => match rest[1..].chars().next() {
+                    Some(escaped @ ('"' | '\\')) => {
+                        value.push(escaped);
+                        rest = &rest[1 + escaped.len_utf8()..];
+                    }
+                    _ => {
+                        return Err(NomError(E::from_error_kind(
+                            rest,
+                            nom::error::ErrorKind::Escaped,
+                        )))
+                    }
+                },
+                Some(c) => {
+                    value.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+                None => {
+                    return Err(NomError(E::from_error_kind(
+                        rest,
+                        nom::error::ErrorKind::Tag,
+                    )))
+                }
+            }
+        }
+    }
+}
+
+/// Parses an image reference into its registry, repository, tag and digest
+/// components.
+///
+/// The first `/`-separated component is only treated as a registry host if
+/// it contains a `.` or a `:`, or is literally `localhost` - otherwise it is
+/// the first component of the repository path. This mirrors the
+/// disambiguation rule used by `docker` itself.
+fn image_ref<'a, E: ParseError<&'a str>>(
+) -> impl FnMut(&'a str) -> IResult<&'a str, ImageRef<'a>, E> {
+    move |input: &'a str| {
+        let (rest, registry) = opt(registry())(input)?;
+        let (rest, repository) = repository()(rest)?;
+        let (rest, tag) = opt(tag())(rest)?;
+        let (rest, digest) = opt(digest())(rest)?;
+        let consumed = input.len() - rest.len();
+        Ok((
+            rest,
+            ImageRef {
+                raw: &input[..consumed],
+                registry,
+                repository,
+                tag,
+                digest,
+            },
+        ))
+    }
+}
+
+/// Parses the `AS <name>` clause that names a build stage in a multi-stage
+/// `FROM`, e.g. `AS builder` in `FROM golang:1.21 AS builder`. Case-insensitive,
+/// matching Docker's own handling of the `AS` keyword.
+fn stage_alias<'a, E: ParseError<&'a str>>(
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |input: &'a str| {
+        let (rest, keyword) = alpha1(input)?;
+        if !keyword.eq_ignore_ascii_case("as") {
+            return Err(NomError(E::from_error_kind(input, nom::error::ErrorKind::Tag)));
+        }
+        let (rest, _) = space1(rest)?;
+        take_while1(|c: char| !c.is_whitespace())(rest)
+    }
+}
+
+fn registry<'a, E: ParseError<&'a str>>() -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |input: &'a str| {
+        let (rest, component) = is_not("/\r\n")(input)?;
+        if component.contains('.') || component.contains(':') || component == "localhost" {
+            let (rest, _) = char1('/')(rest)?;
+            Ok((rest, component))
+        } else {
+            Err(NomError(E::from_error_kind(
+                input,
+                nom::error::ErrorKind::Verify,
+            )))
+        }
+    }
 }
 
-fn ws<'a, F: 'a, O, E: ParseError<&'a str>>(
+fn repository<'a, E: ParseError<&'a str>>(
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    recognize(separated_list1(char1('/'), repository_component()))
+}
+
+fn repository_component<'a, E: ParseError<&'a str>>(
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    take_while1(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || "._-".contains(c))
+}
+
+fn tag<'a, E: ParseError<&'a str>>() -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    preceded(
+        char1(':'),
+        take_while1(|c: char| c != '@' && !c.is_whitespace()),
+    )
+}
+
+fn digest<'a, E: ParseError<&'a str>>() -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    preceded(
+        char1('@'),
+        recognize(tuple((
+            take_while1(|c: char| c.is_ascii_alphanumeric()),
+            char1(':'),
+            take_while1(|c: char| c.is_ascii_hexdigit()),
+        ))),
+    )
+}
+
+fn ws<'a, F, O, E: ParseError<&'a str>>(
     inner: F,
 ) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+    F: FnMut(&'a str) -> IResult<&'a str, O, E> + 'a,
 {
     delimited(multispace0, inner, multispace0)
 }
@@ -117,31 +525,82 @@ mod tests {
     use const_format::formatcp;
     use proptest::prelude::*;
 
-    const DOMAIN_AND_PORT_REGEX: &str = r#"(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z0-9][a-z0-9-]{0,61}[a-z0-9]:[0-9]{0,5}/)"#;
+    use super::parse_instruction;
+
+    const DOMAIN_AND_PORT_REGEX: &str =
+        r#"(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z0-9][a-z0-9-]{0,61}[a-z0-9]:[0-9]{1,5}"#;
+    const REPOSITORY_REGEX: &str = r#"([a-z0-9]+[._-]?)+[a-z0-9]+(?:/([a-z0-9]+[._-]?)+[a-z0-9]+){0,2}"#;
+    const TAG_REGEX: &str = r#"([a-z0-9]+[._-]?)+[a-z0-9]+"#;
+    const DIGEST_REGEX: &str = r#"[a-z0-9]+:[0-9a-f]{8,64}"#;
     const IMAGE_NAME_REGEX: &str = formatcp!(
-        "({}?([a-z0-9]+[._-]?)+[a-z0-9]+:([a-z0-9]+[._-]?)+[a-z0-9]+",
-        DOMAIN_AND_PORT_REGEX
+        "(?:{}/)?{}:{}(?:@{})?",
+        DOMAIN_AND_PORT_REGEX,
+        REPOSITORY_REGEX,
+        TAG_REGEX,
+        DIGEST_REGEX
     );
+    // Printable, non-newline, not starting with `[` so generated shell
+    // commands are never mistaken for the exec form's JSON array.
+    const RUN_SHELL_COMMAND_REGEX: &str = r#"[!-Z\]-~]+(?: [!-~]+){0,4}"#;
+    const RUN_EXEC_ARG_REGEX: &str = r#"[a-zA-Z0-9/_.-]{1,10}"#;
 
-    /// Generates a FROM instruction.
-    fn arbitrary_from() -> impl Strategy<Value = (String, String)> {
+    /// Generates a FROM instruction line.
+    fn arbitrary_from() -> impl Strategy<Value = String> {
         proptest::string::string_regex(IMAGE_NAME_REGEX)
             .expect("failed to generate strategy")
-            .prop_map(|s| (format!("FROM {}", s), s))
+            .prop_map(|s| format!("FROM {}", s))
+            .boxed()
+    }
+
+    /// Generates a RUN instruction line using the shell form.
+    fn arbitrary_run_shell() -> impl Strategy<Value = String> {
+        proptest::string::string_regex(RUN_SHELL_COMMAND_REGEX)
+            .expect("failed to generate strategy")
+            .prop_map(|s| format!("RUN {}", s))
             .boxed()
     }
 
-    //    proptest! {
-    //         #[test]
-    //         fn from_instruction_parses_correctly((from_instruction, expected_image) in arbitrary_from()) {
-    //            let result = from(&from_instruction).unwrap();
-    //            assert_eq!(
-    //                result.1,
-    //                From{
-    //                    image: &expected_image
-    //                }
-    //            );
-    //            assert_eq!(result.0, "");
-    //        }
-    //    }
+    /// Generates a RUN instruction line using the exec form.
+    fn arbitrary_run_exec() -> impl Strategy<Value = String> {
+        proptest::collection::vec(
+            proptest::string::string_regex(RUN_EXEC_ARG_REGEX)
+                .expect("failed to generate strategy"),
+            1..4,
+        )
+        .prop_map(|args| {
+            let args = args
+                .iter()
+                .map(|arg| format!("{:?}", arg))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("RUN [{}]", args)
+        })
+        .boxed()
+    }
+
+    proptest! {
+        #[test]
+        fn from_instruction_round_trips(line in arbitrary_from()) {
+            let (_, original) = parse_instruction(&line).expect("generated line should parse");
+            let rendered = original.to_string();
+            let (_, reparsed) = parse_instruction(&rendered).expect("rendered instruction should reparse");
+            assert_eq!(original, reparsed);
+        }
+
+        #[test]
+        fn run_shell_instruction_round_trips(line in arbitrary_run_shell()) {
+            let (_, original) = parse_instruction(&line).expect("generated line should parse");
+            let rendered = original.to_string();
+            let (_, reparsed) = parse_instruction(&rendered).expect("rendered instruction should reparse");
+            assert_eq!(original, reparsed);
+        }
+
+        #[test]
+        fn run_exec_instruction_round_trips(line in arbitrary_run_exec()) {
+            let (_, original) = parse_instruction(&line).expect("generated line should parse");
+            let rendered = original.to_string();
+            let (_, reparsed) = parse_instruction(&rendered).expect("rendered instruction should reparse");
+            assert_eq!(original, reparsed);
+        }
+    }
 }
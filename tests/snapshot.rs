@@ -0,0 +1,69 @@
+use dockerfile_parser::{Dockerfile, SyntaxError};
+use std::fs;
+use std::path::Path;
+
+/// Renders the parsed instruction tree and any diagnostics into the text
+/// that gets compared against a fixture's committed `.snapshot` file.
+fn dump(dockerfile: &Dockerfile, errors: &[SyntaxError]) -> String {
+    format!(
+        "instructions:\n{:#?}\nerrors:\n{:#?}\n",
+        dockerfile.instructions, errors
+    )
+}
+
+/// Parses every `.dockerfile` fixture in `dir` and compares its dump against
+/// the sibling `.snapshot` file, regenerating snapshots when
+/// `UPDATE_SNAPSHOTS` is set.
+fn run_corpus(dir: &str, expect_errors: bool) {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+    for entry in fs::read_dir(&dir).expect("fixture directory should exist") {
+        let path = entry.expect("directory entry should be readable").path();
+        if path.extension().and_then(|e| e.to_str()) != Some("dockerfile") {
+            continue;
+        }
+
+        let input = fs::read_to_string(&path).expect("fixture should be readable");
+        let (dockerfile, errors) = Dockerfile::parse(&input);
+
+        if expect_errors {
+            assert!(
+                !errors.is_empty(),
+                "{} should produce at least one syntax error",
+                path.display()
+            );
+        } else {
+            assert!(
+                errors.is_empty(),
+                "{} should parse without syntax errors, got {:#?}",
+                path.display(),
+                errors
+            );
+        }
+
+        let snapshot_path = path.with_extension("snapshot");
+        let actual = dump(&dockerfile, &errors);
+        if update {
+            fs::write(&snapshot_path, &actual).expect("should write snapshot");
+            continue;
+        }
+
+        let expected = fs::read_to_string(&snapshot_path).unwrap_or_else(|_| {
+            panic!(
+                "missing snapshot {} - run with UPDATE_SNAPSHOTS=1 to generate it",
+                snapshot_path.display()
+            )
+        });
+        assert_eq!(actual, expected, "snapshot mismatch for {}", path.display());
+    }
+}
+
+#[test]
+fn ok_fixtures_parse_without_errors() {
+    run_corpus("tests/data/parser/ok", false);
+}
+
+#[test]
+fn err_fixtures_produce_syntax_errors() {
+    run_corpus("tests/data/parser/err", true);
+}